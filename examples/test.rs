@@ -1,9 +1,7 @@
-#![allow(static_mut_refs)]
-
 use rolling_threadsafe_buffer::RollingBuffer;
 use std::collections::HashSet;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::thread::{sleep, spawn};
+use std::thread::{scope, sleep};
 use std::time::Duration;
 
 const ITER_C: usize = 60;
@@ -12,78 +10,80 @@ fn main() {
     snake();
 }
 
+#[allow(dead_code)]
 fn other() {
-    static KILL: AtomicBool = AtomicBool::new(false);
-    static mut DATA: RollingBuffer<25, i32> = RollingBuffer::new(0);
-
-    let read = spawn(|| {
-        let mut seen = HashSet::new();
-        loop {
-            if KILL.load(Ordering::SeqCst) {
-                break;
-            };
+    let kill = AtomicBool::new(false);
+    let mut buffer: RollingBuffer<25, i32> = RollingBuffer::new();
+    let (mut tx, mut rx) = buffer.split();
+
+    scope(|s| {
+        s.spawn(|| {
+            let mut seen = HashSet::new();
+            loop {
+                if kill.load(Ordering::SeqCst) {
+                    break;
+                };
 
-            if let Some(val) = unsafe { DATA.read() } {
-                println!("READ {val}");
-                if seen.contains(val) {
-                    panic!()
+                if let Some(val) = rx.read() {
+                    println!("READ {val}");
+                    if seen.contains(&val) {
+                        panic!()
+                    } else {
+                        seen.insert(val);
+                    };
                 } else {
-                    seen.insert(*val);
-                };
-            } else {
-                print!(" | ");
-            }
+                    print!(" | ");
+                }
 
-            sleep(Duration::from_millis(2));
-        }
-    });
+                sleep(Duration::from_millis(2));
+            }
+        });
 
-    let write = spawn(|| {
-        let mut i = 0;
-        loop {
-            if KILL.load(Ordering::SeqCst) {
-                break;
-            };
+        s.spawn(|| {
+            let mut i = 0;
+            loop {
+                if kill.load(Ordering::SeqCst) {
+                    break;
+                };
 
-            while !unsafe { DATA.write(i) } {
-                sleep(Duration::from_millis(10))
-            }
-            println!("Wrote {i}");
+                while !tx.write(i) {
+                    sleep(Duration::from_millis(10))
+                }
+                println!("Wrote {i}");
 
-            i += 1;
-            sleep(Duration::from_millis(5));
+                i += 1;
+                sleep(Duration::from_millis(5));
 
-            if i >= ITER_C as i32 {
-                KILL.store(true, Ordering::SeqCst);
-            };
-        }
+                if i >= ITER_C as i32 {
+                    kill.store(true, Ordering::SeqCst);
+                };
+            }
+        });
     });
-
-    read.join().unwrap();
-    write.join().unwrap();
 }
 
 fn snake() {
-    let mut buffer: RollingBuffer<10, i32> = RollingBuffer::new(0);
+    let mut buffer: RollingBuffer<10, i32> = RollingBuffer::new();
+    let (mut tx, mut rx) = buffer.split();
 
     for c in 0..5 {
         // Write as much as possible
         for i in 0..9 {
-            assert!(buffer.write(i), "Index {i} iter {c}");
+            assert!(tx.write(i), "Index {i} iter {c}");
         }
         for _ in 0..50 {
-            assert_eq!(buffer.write(0), false);
+            assert_eq!(tx.write(0), false);
         }
 
         // Read as much as possible
         for i in 0..9 {
-            assert_eq!(buffer.read(), Some(&i), "Index {i} iter {c}");
+            assert_eq!(rx.read(), Some(i), "Index {i} iter {c}");
         }
         for _ in 0..50 {
-            assert_eq!(buffer.read(), None);
+            assert_eq!(rx.read(), None);
         }
 
         // Buffer should now be empty
-        assert_eq!(buffer.read(), None);
+        assert_eq!(rx.read(), None);
     }
 }
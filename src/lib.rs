@@ -1,21 +1,35 @@
 #![cfg_attr(not(test), no_std)]
 
-use core::sync::atomic::AtomicBool;
-use core::option::Option::{None, Some};
-use core::marker::Send;
-
-
-pub struct RollingBuffer<const S: usize, T: Default + Copy> {
-    data: [T; S],
-    write_head: usize,
-    read_head: usize,
+use core::cell::UnsafeCell;
+use core::marker::{PhantomData, Send};
+use core::mem::MaybeUninit;
+use core::option::Option::{self, None, Some};
+use core::ptr::{self, NonNull};
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+
+pub struct RollingBuffer<const S: usize, T> {
+    data: UnsafeCell<[MaybeUninit<T>; S]>,
+    write_head: AtomicUsize,
+    read_head: AtomicUsize,
 }
-impl<const S: usize, T: Default + Copy> RollingBuffer<S, T> {
-    pub const fn new(def: T) -> Self {
+
+// The single-producer/single-consumer contract is a type invariant, not a
+// convention: the only safe way to enqueue or dequeue is through the one
+// `Producer` and one `Consumer` handed out by `split` (the `unsafe`
+// `write_unchecked`/`read_unchecked` leave this to their caller). Because there
+// is exactly one of each, safe code can never have two threads both push (racing
+// `write_head`) or both pop (racing `read_head`), so the producer and consumer
+// always touch disjoint slots. That
+// makes the shared access race-free as long as `T` can cross a thread boundary.
+unsafe impl<const S: usize, T: Send> Sync for RollingBuffer<S, T> {}
+
+impl<const S: usize, T> RollingBuffer<S, T> {
+    pub const fn new() -> Self {
         Self {
-            data: [def; S],
-            write_head: 0,
-            read_head: 0,
+            data: UnsafeCell::new([const { MaybeUninit::uninit() }; S]),
+            write_head: AtomicUsize::new(0),
+            read_head: AtomicUsize::new(0),
         }
     }
 
@@ -25,50 +39,266 @@ impl<const S: usize, T: Default + Copy> RollingBuffer<S, T> {
         if t == S { 0 } else { t }
     }
 
+    /// Splits the buffer into the single [`Producer`] and single [`Consumer`]
+    /// that own its write and read ends. Holding exactly one of each is what
+    /// turns single-producer/single-consumer into a compile-time guarantee: the
+    /// mutable borrow lasts as long as the handles, and the safe push/pop methods
+    /// live only on the handles.
+    pub fn split(&mut self) -> (Producer<'_, S, T>, Consumer<'_, S, T>) {
+        let rb = NonNull::from(&*self);
+        (
+            Producer { rb, _marker: PhantomData },
+            Consumer { rb, _marker: PhantomData },
+        )
+    }
+
     /// May loop read head if not called at a slower rate then read
     #[inline(always)]
-    pub unsafe fn write_unchecked(&mut self, data: T) {
-        self.data[self.write_head] = data;
-        self.write_head = Self::increase_head(self.write_head);
+    pub unsafe fn write_unchecked(&self, data: T) {
+        let head = self.write_head.load(Ordering::Relaxed);
+        (*self.data.get())[head].write(data);
+        self.write_head.store(Self::increase_head(head), Ordering::Release);
     }
 
     /// May loop write head if not called at a faster rate then write
     #[inline(always)]
-    pub unsafe fn read_unchecked(&mut self) -> Option<&T> {
-        if self.read_head != self.write_head {
-            let ret = Some(&self.data[self.read_head]);
-            self.read_head = Self::increase_head(self.read_head);
-            ret
+    pub unsafe fn read_unchecked(&self) -> Option<T> {
+        let head = self.read_head.load(Ordering::Relaxed);
+        if head != self.write_head.load(Ordering::Acquire) {
+            let val = (*self.data.get())[head].assume_init_read();
+            self.read_head.store(Self::increase_head(head), Ordering::Release);
+            Some(val)
         } else {
             None
         }
     }
+}
+
+impl<const S: usize, T> Default for RollingBuffer<S, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const S: usize, T> Drop for RollingBuffer<S, T> {
+    fn drop(&mut self) {
+        // Drop only the live elements, i.e. the run from `read_head` up to
+        // `write_head`. `&mut self` means no other thread can be racing us.
+        let mut head = *self.read_head.get_mut();
+        let write = *self.write_head.get_mut();
+        let buf = self.data.get_mut();
+        while head != write {
+            unsafe { buf[head].assume_init_drop() };
+            head = Self::increase_head(head);
+        }
+    }
+}
+
+/// The write end of a [`RollingBuffer`], obtained from [`RollingBuffer::split`].
+/// There is only ever one, so it is the sole writer of `write_head`.
+pub struct Producer<'a, const S: usize, T> {
+    rb: NonNull<RollingBuffer<S, T>>,
+    _marker: PhantomData<&'a RollingBuffer<S, T>>,
+}
+
+// The producer's safe methods only touch the write end, which no other handle
+// touches, so it is safe to move to another thread whenever `T` can. The
+// `unsafe` `write_overwrite` also moves the read end; its caller excludes a
+// concurrent consumer.
+unsafe impl<const S: usize, T: Send> Send for Producer<'_, S, T> {}
+
+impl<const S: usize, T> Producer<'_, S, T> {
+    #[inline(always)]
+    fn rb(&self) -> &RollingBuffer<S, T> {
+        // `split` borrows the buffer for `'a`, so it outlives this handle.
+        unsafe { self.rb.as_ref() }
+    }
 
     /// returns false if read head doesn't keep up
     #[inline(always)]
     pub fn write(&mut self, data: T) -> bool {
-        let next = Self::increase_head(self.write_head);
-        if next == self.read_head {
+        let rb = self.rb();
+        let head = rb.write_head.load(Ordering::Relaxed);
+        let next = RollingBuffer::<S, T>::increase_head(head);
+        if next == rb.read_head.load(Ordering::Acquire) {
             return false
         };
 
-        self.data[self.write_head] = data;
-        self.write_head = next;
+        // Only this handle writes `write_head`, so the slot at `head` is ours to
+        // move into before we publish it with the `Release` store below. The slot
+        // is uninitialized (the consumer moved its old value out on `read`).
+        unsafe { (*rb.data.get())[head].write(data) };
+        rb.write_head.store(next, Ordering::Release);
 
         true
     }
 
+    /// Writes `data`, overwriting the oldest element when the buffer is full so
+    /// the most recent `S - 1` samples are always kept. Unlike [`write`](Self::write)
+    /// this never refuses a value.
+    ///
+    /// This is a **single-threaded-only** operation: advancing `read_head` from
+    /// the writer cannot be done soundly against a live reader, so the concurrent
+    /// "slow consumer sees fresh data" pattern is *not* supported here — for that,
+    /// overwrite on the producer side is the wrong tool. Use it only when the same
+    /// thread both writes and reads, or with external synchronization serializing
+    /// it against every read.
+    ///
+    /// # Safety
+    ///
+    /// When full this advances `read_head` and drops the oldest slot, so it reads
+    /// and mutates the same slot the [`Consumer`]'s [`read`](Consumer::read),
+    /// [`read_slice`](Consumer::read_slice) or [`peek`](Consumer::peek) would
+    /// touch. The caller must guarantee no consumer runs concurrently; violating
+    /// this is a data race and a double-drop.
     #[inline(always)]
-    pub fn read(&mut self) -> Option<&T> {
-        if self.read_head != self.write_head {
-            let next = Self::increase_head(self.read_head);
-            let ret = Some(&self.data[self.read_head]);
-            self.read_head = next;
-            ret
+    pub unsafe fn write_overwrite(&mut self, data: T) {
+        let rb = self.rb();
+        let head = rb.write_head.load(Ordering::Relaxed);
+        let next = RollingBuffer::<S, T>::increase_head(head);
+        let read = rb.read_head.load(Ordering::Acquire);
+        if next == read {
+            // Full: drop the oldest element and step the read head past it.
+            (*rb.data.get())[read].assume_init_drop();
+            rb.read_head.store(RollingBuffer::<S, T>::increase_head(read), Ordering::Release);
+        }
+
+        (*rb.data.get())[head].write(data);
+        rb.write_head.store(next, Ordering::Release);
+    }
+}
+
+impl<const S: usize, T: Copy> Producer<'_, S, T> {
+    /// Copies as many elements from `src` as currently fit, returning the count
+    /// written. Done in at most two `copy_nonoverlapping` calls (one per side of
+    /// the wrap boundary) and a single head publish.
+    pub fn write_slice(&mut self, src: &[T]) -> usize {
+        let rb = self.rb();
+        let head = rb.write_head.load(Ordering::Relaxed);
+        let read = rb.read_head.load(Ordering::Acquire);
+        let free = S - 1 - (head + S - read) % S;
+        let n = if src.len() < free { src.len() } else { free };
+        if n == 0 {
+            return 0;
+        }
+
+        // `MaybeUninit<T>` is layout-compatible with `T`, so the slot array can
+        // be addressed as a `*mut T` for the bulk copy.
+        let dst = unsafe { (*rb.data.get()).as_mut_ptr() as *mut T };
+        let first = if n < S - head { n } else { S - head };
+        unsafe {
+            ptr::copy_nonoverlapping(src.as_ptr(), dst.add(head), first);
+            if n > first {
+                ptr::copy_nonoverlapping(src.as_ptr().add(first), dst, n - first);
+            }
+        }
+
+        rb.write_head.store((head + n) % S, Ordering::Release);
+        n
+    }
+}
+
+/// The read end of a [`RollingBuffer`], obtained from [`RollingBuffer::split`].
+/// There is only ever one, so apart from [`Producer::write_overwrite`] it is the
+/// sole writer of `read_head`.
+pub struct Consumer<'a, const S: usize, T> {
+    rb: NonNull<RollingBuffer<S, T>>,
+    _marker: PhantomData<&'a RollingBuffer<S, T>>,
+}
+
+// The consumer only touches the read end, which no other handle touches outside
+// the `unsafe` `Producer::write_overwrite`, so it is safe to move to another thread whenever `T` can.
+unsafe impl<const S: usize, T: Send> Send for Consumer<'_, S, T> {}
+
+impl<const S: usize, T> Consumer<'_, S, T> {
+    #[inline(always)]
+    fn rb(&self) -> &RollingBuffer<S, T> {
+        // `split` borrows the buffer for `'a`, so it outlives this handle.
+        unsafe { self.rb.as_ref() }
+    }
+
+    #[inline(always)]
+    pub fn read(&mut self) -> Option<T> {
+        let rb = self.rb();
+        let head = rb.read_head.load(Ordering::Relaxed);
+        if head != rb.write_head.load(Ordering::Acquire) {
+            let next = RollingBuffer::<S, T>::increase_head(head);
+            // The producer published this slot with a `Release` store, so the
+            // value is fully initialized and ours to move out.
+            let val = unsafe { (*rb.data.get())[head].assume_init_read() };
+            rb.read_head.store(next, Ordering::Release);
+            Some(val)
         } else {
             None
         }
     }
+
+    /// Returns a reference to the next readable element without advancing the
+    /// read head, or `None` when empty. Takes `&mut self` like [`read`](Self::read),
+    /// so the borrow excludes a concurrent dequeue: the compiler forbids holding
+    /// the returned reference across a `read` that would move the value out of
+    /// that slot (what would otherwise be a safe use-after-move / use-after-free).
+    #[inline(always)]
+    pub fn peek(&mut self) -> Option<&T> {
+        let rb = self.rb();
+        let head = rb.read_head.load(Ordering::Relaxed);
+        if head != rb.write_head.load(Ordering::Acquire) {
+            Some(unsafe { (*rb.data.get())[head].assume_init_ref() })
+        } else {
+            None
+        }
+    }
+
+    /// Number of elements currently available to read.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        let rb = self.rb();
+        let read = rb.read_head.load(Ordering::Relaxed);
+        let write = rb.write_head.load(Ordering::Acquire);
+        (write + S - read) % S
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        let rb = self.rb();
+        rb.read_head.load(Ordering::Relaxed) == rb.write_head.load(Ordering::Acquire)
+    }
+
+    #[inline(always)]
+    pub fn is_full(&self) -> bool {
+        let rb = self.rb();
+        let write = rb.write_head.load(Ordering::Relaxed);
+        let read = rb.read_head.load(Ordering::Acquire);
+        RollingBuffer::<S, T>::increase_head(write) == read
+    }
+}
+
+impl<const S: usize, T: Copy> Consumer<'_, S, T> {
+    /// Copies as many readable elements into `dst` as are available, returning
+    /// the count read. Mirrors [`Producer::write_slice`]: at most two
+    /// `copy_nonoverlapping` calls and a single head publish.
+    pub fn read_slice(&mut self, dst: &mut [T]) -> usize {
+        let rb = self.rb();
+        let head = rb.read_head.load(Ordering::Relaxed);
+        let write = rb.write_head.load(Ordering::Acquire);
+        let used = (write + S - head) % S;
+        let n = if dst.len() < used { dst.len() } else { used };
+        if n == 0 {
+            return 0;
+        }
+
+        let src = unsafe { (*rb.data.get()).as_ptr() as *const T };
+        let first = if n < S - head { n } else { S - head };
+        unsafe {
+            ptr::copy_nonoverlapping(src.add(head), dst.as_mut_ptr(), first);
+            if n > first {
+                ptr::copy_nonoverlapping(src, dst.as_mut_ptr().add(first), n - first);
+            }
+        }
+
+        rb.read_head.store((head + n) % S, Ordering::Release);
+        n
+    }
 }
 
 #[cfg(test)]
@@ -77,234 +307,222 @@ mod tests {
 
     #[test]
     fn just_write_unchecked() {
-        let mut buff: RollingBuffer<4, usize> = RollingBuffer::new(0);
+        let buff: RollingBuffer<4, usize> = RollingBuffer::new();
         unsafe {
             buff.write_unchecked(1);
             buff.write_unchecked(2);
             buff.write_unchecked(3);
-            assert_eq!(buff.data, [1, 2, 3, 0]);
+            let d = &*buff.data.get();
+            assert_eq!(d[0].assume_init_read(), 1);
+            assert_eq!(d[1].assume_init_read(), 2);
+            assert_eq!(d[2].assume_init_read(), 3);
 
             buff.write_unchecked(4);
             buff.write_unchecked(5);
-            assert_eq!(buff.data, [5, 2, 3, 4]);
+            let d = &*buff.data.get();
+            assert_eq!(
+                [
+                    d[0].assume_init_read(),
+                    d[1].assume_init_read(),
+                    d[2].assume_init_read(),
+                    d[3].assume_init_read(),
+                ],
+                [5, 2, 3, 4]
+            );
         }
     }
 
     #[test]
     fn read_write_unchecked() {
-        let mut buff: RollingBuffer<4, usize> = RollingBuffer::new(0);
+        let buff: RollingBuffer<4, usize> = RollingBuffer::new();
         unsafe {
             buff.write_unchecked(1);
             buff.write_unchecked(2);
             buff.write_unchecked(3);
-            assert_eq!(buff.data, [1, 2, 3, 0]);
 
-            assert_eq!(buff.read_unchecked(), Some(&1));
-            assert_eq!(buff.read_unchecked(), Some(&2));
-            assert_eq!(buff.read_unchecked(), Some(&3));
+            assert_eq!(buff.read_unchecked(), Some(1));
+            assert_eq!(buff.read_unchecked(), Some(2));
+            assert_eq!(buff.read_unchecked(), Some(3));
             assert_eq!(buff.read_unchecked(), None);
 
             buff.write_unchecked(54);
-            assert_eq!(buff.read_unchecked(), Some(&54));
+            assert_eq!(buff.read_unchecked(), Some(54));
         }
     }
 
     #[test]
     fn just_write_checked() {
-        let mut buff: RollingBuffer<5, usize> = RollingBuffer::new(0);
-        buff.write(1);
-        buff.write(2);
-        buff.write(3);
-        assert_eq!(buff.write(4), true);
-        assert_eq!(buff.write(5), false);
+        let mut buff: RollingBuffer<5, usize> = RollingBuffer::new();
+        let (mut tx, _rx) = buff.split();
+        tx.write(1);
+        tx.write(2);
+        tx.write(3);
+        assert_eq!(tx.write(4), true);
+        assert_eq!(tx.write(5), false);
     }
 
     #[test]
     fn read_write_checked() {
-        let mut buff: RollingBuffer<4, usize> = RollingBuffer::new(0);
-        buff.write(1);
-        buff.write(2);
-        assert_eq!(buff.read(), Some(&1));
-        buff.write(3);
-        assert_eq!(buff.write(4), true);
-
-        // assert_eq!(buff.write(5), true);
-        // assert_eq!(buff.write(6), false);
+        let mut buff: RollingBuffer<4, usize> = RollingBuffer::new();
+        let (mut tx, mut rx) = buff.split();
+        tx.write(1);
+        tx.write(2);
+        assert_eq!(rx.read(), Some(1));
+        tx.write(3);
+        assert_eq!(tx.write(4), true);
+
+        // assert_eq!(tx.write(5), true);
+        // assert_eq!(tx.write(6), false);
     }
 
     #[test]
     fn snake() {
-        let mut buffer: RollingBuffer<10, i32> = RollingBuffer::new(0);
+        let mut buffer: RollingBuffer<10, i32> = RollingBuffer::new();
+        let (mut tx, mut rx) = buffer.split();
 
         for c in 0..5 {
             // Write as much as possible
             for i in 0..9 {
-                assert!(buffer.write(i), "Index {i} iter {c}");
+                assert!(tx.write(i), "Index {i} iter {c}");
             }
             for _ in 0..50 {
-                assert_eq!(buffer.write(0), false);
+                assert_eq!(tx.write(0), false);
             }
 
             // Read as much as possible
             for i in 0..9 {
-                assert_eq!(buffer.read(), Some(&i), "Index {i} iter {c}");
+                assert_eq!(rx.read(), Some(i), "Index {i} iter {c}");
             }
             for _ in 0..50 {
-                assert_eq!(buffer.read(), None);
+                assert_eq!(rx.read(), None);
             }
 
             // Buffer should now be empty
-            assert_eq!(buffer.read(), None);
+            assert_eq!(rx.read(), None);
         }
     }
 
+    #[test]
+    fn holds_move_only_payload() {
+        let mut buffer: RollingBuffer<4, String> = RollingBuffer::new();
+        let (mut tx, mut rx) = buffer.split();
+        assert!(tx.write(String::from("a")));
+        assert!(tx.write(String::from("b")));
+        assert_eq!(rx.read(), Some(String::from("a")));
+        assert_eq!(rx.read(), Some(String::from("b")));
+        assert_eq!(rx.read(), None);
+
+        // A value left in the buffer must be dropped by `Drop`, not leaked.
+        assert!(tx.write(String::from("c")));
+    }
+
+    #[test]
+    fn peek_and_occupancy() {
+        let mut buffer: RollingBuffer<4, i32> = RollingBuffer::new();
+        let (mut tx, mut rx) = buffer.split();
+        assert!(rx.is_empty());
+        assert_eq!(rx.len(), 0);
+        assert_eq!(rx.peek(), None);
+
+        tx.write(10);
+        tx.write(20);
+        assert_eq!(rx.len(), 2);
+        assert!(!rx.is_empty());
+
+        // peek reports the oldest element without consuming it.
+        assert_eq!(rx.peek(), Some(&10));
+        assert_eq!(rx.peek(), Some(&10));
+
+        tx.write(30);
+        assert!(rx.is_full());
+        assert_eq!(rx.len(), 3);
+        assert_eq!(tx.write(40), false);
+
+        assert_eq!(rx.read(), Some(10));
+        assert!(!rx.is_full());
+    }
+
+    #[test]
+    fn overwrite_keeps_newest() {
+        let mut buffer: RollingBuffer<4, i32> = RollingBuffer::new();
+        let (mut tx, mut rx) = buffer.split();
+
+        // Single-threaded use: no consumer runs concurrently, so the `unsafe`
+        // contract of `write_overwrite` is upheld. Usable capacity is S - 1 = 3,
+        // so writing 5 values keeps the last 3.
+        unsafe {
+            for i in 0..5 {
+                tx.write_overwrite(i);
+            }
+        }
+
+        assert_eq!(rx.read(), Some(2));
+        assert_eq!(rx.read(), Some(3));
+        assert_eq!(rx.read(), Some(4));
+        assert_eq!(rx.read(), None);
+    }
+
+    #[test]
+    fn slice_fills_up_to_capacity() {
+        let mut buffer: RollingBuffer<5, i32> = RollingBuffer::new();
+        let (mut tx, mut rx) = buffer.split();
+
+        // Only S - 1 slots are usable, so the last source element is refused.
+        assert_eq!(tx.write_slice(&[1, 2, 3, 4, 5]), 4);
+        assert_eq!(tx.write_slice(&[6]), 0);
+
+        let mut dst = [0; 8];
+        assert_eq!(rx.read_slice(&mut dst), 4);
+        assert_eq!(dst[..4], [1, 2, 3, 4]);
+        assert_eq!(rx.read_slice(&mut dst), 0);
+    }
+
+    #[test]
+    fn slice_copies_across_the_wrap() {
+        let mut buffer: RollingBuffer<5, i32> = RollingBuffer::new();
+        let (mut tx, mut rx) = buffer.split();
+
+        // Advance both heads so the next run straddles the end of the array.
+        assert_eq!(tx.write_slice(&[1, 2, 3]), 3);
+        let mut dst = [0; 3];
+        assert_eq!(rx.read_slice(&mut dst), 3);
+
+        assert_eq!(tx.write_slice(&[4, 5, 6, 7]), 4);
+        let mut dst = [0; 4];
+        assert_eq!(rx.read_slice(&mut dst), 4);
+        assert_eq!(dst, [4, 5, 6, 7]);
+    }
+
     #[cfg(test)]
     mod threaded {
         use super::*;
         use std::collections::HashSet;
-        use std::sync::atomic::Ordering;
-        use std::thread::{sleep, spawn};
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::thread::{scope, sleep};
         use std::time::Duration;
 
         const ITER_C: usize = 60;
 
-        // #[test]
-        // fn write_limited_unchecked() {
-        //     static KILL: AtomicBool = AtomicBool::new(false);
-        //     static DATA: GlobalData<RollingBuffer<25, i32>> =
-        //         GlobalData::new(RollingBuffer::new(0));
-        //
-        //     let read = spawn(|| {
-        //         let r = DATA.get_mut_ref();
-        //         let mut seen = HashSet::new();
-        //         loop {
-        //             if KILL.load(Ordering::SeqCst) {
-        //                 break;
-        //             };
-        //
-        //             if let Some(val) = unsafe { r.read_unchecked() } {
-        //                 // println!("READ {val}");
-        //                 if seen.contains(val) {
-        //                     panic!()
-        //                 } else {
-        //                     seen.insert(*val);
-        //                 };
-        //             }
-        //
-        //             sleep(Duration::from_millis(0));
-        //         }
-        //     });
-        //     let write = spawn(|| {
-        //         let w = DATA.get_mut_ref();
-        //         let mut i = 0;
-        //         loop {
-        //             if KILL.load(Ordering::SeqCst) {
-        //                 break;
-        //             };
-        //
-        //             unsafe {
-        //                 // println!("WRITE {i}");
-        //                 w.write_unchecked(i);
-        //             }
-        //             i += 1;
-        //             sleep(Duration::from_millis(40));
-        //
-        //             if i >= ITER_C as i32 {
-        //                 KILL.store(true, Ordering::SeqCst);
-        //             };
-        //         }
-        //     });
-        //
-        //     read.join().unwrap();
-        //     write.join().unwrap();
-        // }
-        //
-        // #[test]
-        // fn write_limited_chunk_unchecked() {
-        //     static KILL: AtomicBool = AtomicBool::new(false);
-        //     static DATA: GlobalData<RollingBuffer<25, i32>> =
-        //         GlobalData::new(RollingBuffer::new(0));
-        //
-        //     let read = spawn(|| {
-        //         let r = DATA.get_mut_ref();
-        //         let mut seen = HashSet::new();
-        //         loop {
-        //             if KILL.load(Ordering::SeqCst) {
-        //                 break;
-        //             };
-        //
-        //             for _ in 0..6 {
-        //                 if let Some(val) = unsafe { r.read_unchecked() } {
-        //                     // println!("READ {val}");
-        //                     if seen.contains(val) {
-        //                         panic!()
-        //                     } else {
-        //                         seen.insert(*val);
-        //                     };
-        //                 }
-        //             }
-        //
-        //             sleep(Duration::from_millis(30));
-        //         }
-        //     });
-        //     let write = spawn(|| {
-        //         let w = DATA.get_mut_ref();
-        //         let mut i = 0;
-        //         loop {
-        //             if KILL.load(Ordering::SeqCst) {
-        //                 break;
-        //             };
-        //
-        //             unsafe {
-        //                 // println!("WRITE {i}");
-        //                 w.write_unchecked(i);
-        //             }
-        //             i += 1;
-        //             sleep(Duration::from_millis(10));
-        //
-        //             if i >= ITER_C as i32 {
-        //                 KILL.store(true, Ordering::SeqCst);
-        //             };
-        //         }
-        //     });
-        //
-        //     read.join().unwrap();
-        //     write.join().unwrap();
-        // }
-
-        #[cfg(test)]
-        mod safe {
-            #![allow(static_mut_refs)]
-
-            use super::*;
-
-            use std::collections::HashSet;
-            use std::path::Component::ParentDir;
-            use std::sync::atomic::{AtomicBool, Ordering};
-            use std::thread::{sleep, spawn};
-            use std::time::Duration;
-
-            const ITER_C: usize = 60;
-
-            #[test]
-            fn test() {
-                static KILL: AtomicBool = AtomicBool::new(false);
-                static mut DATA: RollingBuffer<25, i32> = RollingBuffer::new(0);
-
-                let read = spawn(|| {
+        #[test]
+        fn test() {
+            let kill = AtomicBool::new(false);
+            let mut buffer: RollingBuffer<25, i32> = RollingBuffer::new();
+            let (mut tx, mut rx) = buffer.split();
+
+            scope(|s| {
+                s.spawn(|| {
                     let mut seen = HashSet::new();
                     loop {
-                        if KILL.load(Ordering::SeqCst) {
+                        if kill.load(Ordering::SeqCst) {
                             break;
                         };
 
-
-                        if let Some(val) = unsafe { DATA.read() } {
+                        if let Some(val) = rx.read() {
                             // println!("READ {val}");
-                            if seen.contains(val) {
+                            if seen.contains(&val) {
                                 panic!()
                             } else {
-                                seen.insert(*val);
+                                seen.insert(val);
                             };
                         } else {
                             // print!(" | ");
@@ -314,14 +532,14 @@ mod tests {
                     }
                 });
 
-                let write = spawn(|| {
+                s.spawn(|| {
                     let mut i = 0;
                     loop {
-                        if KILL.load(Ordering::SeqCst) {
+                        if kill.load(Ordering::SeqCst) {
                             break;
                         };
 
-                        while !unsafe { DATA.write(i) } {
+                        while !tx.write(i) {
                             sleep(Duration::from_millis(10))
                         }
                         // println!("Wrote {i}");
@@ -330,74 +548,66 @@ mod tests {
                         sleep(Duration::from_millis(5));
 
                         if i >= ITER_C as i32 {
-                            KILL.store(true, Ordering::SeqCst);
+                            kill.store(true, Ordering::SeqCst);
                         };
                     }
                 });
+            });
+        }
 
-                read.join().unwrap();
-                write.join().unwrap();
-            }
-
-            #[test]
-            fn snake() {
-                static KILL: AtomicBool = AtomicBool::new(false);
-                static mut DATA: RollingBuffer<25, i32> = RollingBuffer::new(0);
-                static SEL: AtomicBool = AtomicBool::new(false);
+        #[test]
+        fn snake() {
+            let kill = AtomicBool::new(false);
+            let sel = AtomicBool::new(false);
+            let mut buffer: RollingBuffer<25, i32> = RollingBuffer::new();
+            let (mut tx, mut rx) = buffer.split();
 
-                let read = spawn(|| {
+            scope(|s| {
+                s.spawn(|| {
                     'outer: loop {
-                        if KILL.load(Ordering::SeqCst) {
+                        if kill.load(Ordering::SeqCst) {
                             break;
                         }
-                        unsafe {
-                            if SEL.load(Ordering::SeqCst) {
-                                for _ in 0..10 {
-                                    if let None = DATA.read() {
-                                        panic!()
-                                    }
+                        if sel.load(Ordering::SeqCst) {
+                            for _ in 0..10 {
+                                if let None = rx.read() {
+                                    panic!()
                                 }
-                                for _ in 0..53 {
-                                    if let None = DATA.read() {
-                                        SEL.store(false, Ordering::SeqCst);
-                                        continue 'outer;
-                                    }
+                            }
+                            for _ in 0..53 {
+                                if let None = rx.read() {
+                                    sel.store(false, Ordering::SeqCst);
+                                    continue 'outer;
                                 }
-
                             }
                         }
                     }
                 });
 
-                let write = spawn(|| {
+                s.spawn(|| {
                     let mut c = 0;
                     'outer: loop {
                         if c == 5 {
-                            KILL.store(true, Ordering::SeqCst);
+                            kill.store(true, Ordering::SeqCst);
                             break;
                         }
-                        unsafe {
-                            if !SEL.load(Ordering::SeqCst) {
-                                c += 1;
-                                for i in 0..15 {
-                                    if !DATA.write(i) {
-                                        panic!()
-                                    }
+                        if !sel.load(Ordering::SeqCst) {
+                            c += 1;
+                            for i in 0..15 {
+                                if !tx.write(i) {
+                                    panic!()
                                 }
-                                for x in 22..156 {
-                                    if !DATA.write(x) {
-                                        SEL.store(true, Ordering::SeqCst);
-                                        continue 'outer;
-                                    }
+                            }
+                            for x in 22..156 {
+                                if !tx.write(x) {
+                                    sel.store(true, Ordering::SeqCst);
+                                    continue 'outer;
                                 }
                             }
                         }
                     }
                 });
-
-                read.join().unwrap();
-                write.join().unwrap();
-            }
+            });
         }
     }
 }